@@ -0,0 +1,144 @@
+//! Low level, allocation-free CBOR decoding primitives.
+
+use crate::core::marker::{ self, Head, Arg };
+use crate::error::DecodeError;
+
+/// A source that CBOR bytes are read from.
+///
+/// Implementors decide how much of the input they can hand back without
+/// copying: [`Reference::Long`] borrows directly from the underlying input
+/// (lifetime `'de`), while [`Reference::Short`] borrows from a scratch
+/// buffer owned by the reader itself (lifetime `'b`, tied to the `fill`
+/// call). This lets slice-backed readers be fully zero-copy while
+/// `std::io::Read`-backed readers can still work via an internal buffer.
+pub trait Read<'de> {
+    type Error: core::fmt::Debug + core::fmt::Display;
+
+    /// Return at least `want` bytes (or everything left, if there is less
+    /// than `want`) without consuming them.
+    fn fill<'b>(&'b mut self, want: usize) -> Result<Reference<'de, 'b>, Self::Error>;
+
+    /// Consume `n` bytes previously handed out by `fill`.
+    fn advance(&mut self, n: usize);
+}
+
+/// The result of [`Read::fill`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reference<'de, 'b> {
+    /// Borrowed directly from the input, outliving the reader itself.
+    Long(&'de [u8]),
+
+    /// Borrowed from the reader's own scratch space.
+    Short(&'b [u8])
+}
+
+impl<'de, 'b> Reference<'de, 'b> {
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            Reference::Long(buf) => buf,
+            Reference::Short(buf) => buf
+        }
+    }
+}
+
+/// The error type produced by functions in this module, generic over the
+/// underlying [`Read`] implementation's error type.
+pub type Error<E> = DecodeError<E>;
+
+#[inline]
+fn read_slice<'de, 'b, R>(reader: &'b mut R, len: usize, name: &'static str)
+    -> Result<Reference<'de, 'b>, DecodeError<R::Error>>
+where
+    R: Read<'de>
+{
+    let buf = reader.fill(len).map_err(DecodeError::Read)?;
+    if buf.as_slice().len() < len {
+        return Err(DecodeError::Eof { name, expect: len - buf.as_slice().len() });
+    }
+    Ok(buf)
+}
+
+#[inline]
+fn read_array<'de, R, const N: usize>(reader: &mut R, name: &'static str) -> Result<[u8; N], DecodeError<R::Error>>
+where
+    R: Read<'de>
+{
+    let mut out = [0; N];
+    let buf = read_slice(reader, N, name)?;
+    out.copy_from_slice(&buf.as_slice()[..N]);
+    reader.advance(N);
+    Ok(out)
+}
+
+/// Peek at the next byte without consuming it.
+pub fn peek_marker<'de, R>(reader: &mut R) -> Result<u8, DecodeError<R::Error>>
+where
+    R: Read<'de>
+{
+    let buf = read_slice(reader, 1, "marker")?;
+    Ok(buf.as_slice()[0])
+}
+
+/// Decode the next head (major type + argument).
+pub fn read_head<'de, R>(reader: &mut R) -> Result<Head, DecodeError<R::Error>>
+where
+    R: Read<'de>
+{
+    let [first] = read_array::<_, 1>(reader, "marker")?;
+    let major = first >> 5;
+    let info = first & 0x1f;
+
+    // Major 7's additional info doesn't encode an integer argument: 24 is an
+    // extended simple value and 25/26/27 are f16/f32/f64 payload widths, so
+    // the trailing bytes are a value to be reinterpreted, not folded into one.
+    // Leave `info` as-is and let `deserialize_simple` read them itself.
+    let arg = match (major, info) {
+        (marker::MAJOR_SIMPLE, 0 ..= 30) => Arg::Len(info as u64),
+        (marker::MAJOR_SIMPLE, _) => return Err(DecodeError::Unsupported { marker: first }),
+        (_, 0 ..= 23) => Arg::Len(info as u64),
+        (_, marker::INFO_U8) => Arg::Len(u64::from(u8::from_be_bytes(read_array(reader, "u8 argument")?))),
+        (_, marker::INFO_U16) => Arg::Len(u64::from(u16::from_be_bytes(read_array(reader, "u16 argument")?))),
+        (_, marker::INFO_U32) => Arg::Len(u64::from(u32::from_be_bytes(read_array(reader, "u32 argument")?))),
+        (_, marker::INFO_U64) => Arg::Len(u64::from_be_bytes(read_array(reader, "u64 argument")?)),
+        (_, marker::INFO_INDEFINITE) if matches!(major, 2 ..= 5) => Arg::Indefinite,
+        _ => return Err(DecodeError::Unsupported { marker: first })
+    };
+
+    Ok(Head { major, arg })
+}
+
+/// Peek at exactly `len` bytes of raw data (the body of a definite-length
+/// byte or text string chunk) without consuming them.
+///
+/// The caller is responsible for calling [`Read::advance`] once it is done
+/// with the returned reference — a borrowed [`Reference::Short`] can't
+/// outlive a further call into the reader, so this function can't safely
+/// advance on the caller's behalf.
+pub fn peek_bytes<'de, 'b, R>(reader: &'b mut R, len: usize) -> Result<Reference<'de, 'b>, DecodeError<R::Error>>
+where
+    R: Read<'de>
+{
+    read_slice(reader, len, "bytes")
+}
+
+/// Read and discard the `0xff` break stop code.
+pub fn read_break<'de, R>(reader: &mut R) -> Result<(), DecodeError<R::Error>>
+where
+    R: Read<'de>
+{
+    let [marker] = read_array::<_, 1>(reader, "break")?;
+    if marker != marker::BREAK {
+        return Err(DecodeError::Mismatch { name: "break", found: marker });
+    }
+    Ok(())
+}
+
+/// Peek whether the next byte is the `0xff` break stop code, without
+/// consuming it either way.
+pub fn is_break<'de, R>(reader: &mut R) -> Result<bool, DecodeError<R::Error>>
+where
+    R: Read<'de>
+{
+    Ok(peek_marker(reader)? == marker::BREAK)
+}
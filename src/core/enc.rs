@@ -0,0 +1,281 @@
+//! Low level, allocation-free CBOR encoding primitives.
+//!
+//! This module knows nothing about serde; it only knows how to turn heads,
+//! integers, byte strings and so on into bytes on a [`Write`] sink.
+
+use crate::core::marker;
+
+/// A sink that CBOR bytes are written into.
+///
+/// Implemented for `Vec<u8>` (with the `use_alloc` feature) and for
+/// [`SizeWriter`].
+pub trait Write {
+    type Error: core::fmt::Debug + core::fmt::Display;
+
+    /// Append `input` to the sink.
+    fn push(&mut self, input: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// The error type produced by functions in this module.
+pub type Error<W> = crate::error::EncodeError<<W as Write>::Error>;
+
+#[cfg(feature = "use_alloc")]
+impl Write for alloc::vec::Vec<u8> {
+    type Error = core::convert::Infallible;
+
+    #[inline]
+    fn push(&mut self, input: &[u8]) -> Result<(), Self::Error> {
+        self.extend_from_slice(input);
+        Ok(())
+    }
+}
+
+/// A [`Write`] sink that only counts the bytes pushed to it, discarding the
+/// bytes themselves. Lets a caller learn an encoded value's size (to
+/// pre-size a buffer, say) without allocating or writing anything.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SizeWriter {
+    pub size: usize
+}
+
+impl Write for SizeWriter {
+    type Error = core::convert::Infallible;
+
+    #[inline]
+    fn push(&mut self, input: &[u8]) -> Result<(), Self::Error> {
+        self.size += input.len();
+        Ok(())
+    }
+}
+
+#[inline]
+fn write_head_raw<W: Write + ?Sized>(writer: &mut W, major: u8, info: u8) -> Result<(), Error<W>> {
+    writer.push(&[(major << 5) | info]).map_err(crate::error::EncodeError::Write)
+}
+
+/// Write a head (major type + argument) using the shortest possible
+/// encoding of `arg`.
+pub fn write_head<W: Write + ?Sized>(writer: &mut W, major: u8, arg: u64) -> Result<(), Error<W>> {
+    if arg < marker::INFO_U8 as u64 {
+        write_head_raw(writer, major, arg as u8)
+    } else if arg <= u8::MAX as u64 {
+        write_head_raw(writer, major, marker::INFO_U8)?;
+        writer.push(&(arg as u8).to_be_bytes()).map_err(crate::error::EncodeError::Write)
+    } else if arg <= u16::MAX as u64 {
+        write_head_raw(writer, major, marker::INFO_U16)?;
+        writer.push(&(arg as u16).to_be_bytes()).map_err(crate::error::EncodeError::Write)
+    } else if arg <= u32::MAX as u64 {
+        write_head_raw(writer, major, marker::INFO_U32)?;
+        writer.push(&(arg as u32).to_be_bytes()).map_err(crate::error::EncodeError::Write)
+    } else {
+        write_head_raw(writer, major, marker::INFO_U64)?;
+        writer.push(&arg.to_be_bytes()).map_err(crate::error::EncodeError::Write)
+    }
+}
+
+/// Write the indefinite-length head for `major` (bytes/text/array/map).
+pub fn write_indefinite_head<W: Write + ?Sized>(writer: &mut W, major: u8) -> Result<(), Error<W>> {
+    write_head_raw(writer, major, marker::INFO_INDEFINITE)
+}
+
+/// Write the `0xff` break stop code that terminates an indefinite-length
+/// item.
+pub fn write_break<W: Write + ?Sized>(writer: &mut W) -> Result<(), Error<W>> {
+    writer.push(&[marker::BREAK]).map_err(crate::error::EncodeError::Write)
+}
+
+/// Encode an unsigned integer (major type 0).
+pub fn u64<W: Write + ?Sized>(writer: &mut W, value: u64) -> Result<(), Error<W>> {
+    write_head(writer, marker::MAJOR_UINT, value)
+}
+
+/// Encode a signed integer (major type 0 or 1, whichever applies).
+pub fn i64<W: Write + ?Sized>(writer: &mut W, value: i64) -> Result<(), Error<W>> {
+    if value >= 0 {
+        write_head(writer, marker::MAJOR_UINT, value as u64)
+    } else {
+        write_head(writer, marker::MAJOR_NINT, (-1 - value) as u64)
+    }
+}
+
+/// Encode a byte string (major type 2).
+pub fn bytes<W: Write + ?Sized>(writer: &mut W, value: &[u8]) -> Result<(), Error<W>> {
+    write_head(writer, marker::MAJOR_BYTES, value.len() as u64)?;
+    writer.push(value).map_err(crate::error::EncodeError::Write)
+}
+
+/// Encode a UTF-8 text string (major type 3).
+pub fn str<W: Write + ?Sized>(writer: &mut W, value: &str) -> Result<(), Error<W>> {
+    write_head(writer, marker::MAJOR_TEXT, value.len() as u64)?;
+    writer.push(value.as_bytes()).map_err(crate::error::EncodeError::Write)
+}
+
+/// Write a definite-length array head (major type 4); the caller is
+/// responsible for writing exactly `len` elements afterwards.
+pub fn array_head<W: Write + ?Sized>(writer: &mut W, len: u64) -> Result<(), Error<W>> {
+    write_head(writer, marker::MAJOR_ARRAY, len)
+}
+
+/// Write a definite-length map head (major type 5); the caller is
+/// responsible for writing exactly `len` key/value pairs afterwards.
+pub fn map_head<W: Write + ?Sized>(writer: &mut W, len: u64) -> Result<(), Error<W>> {
+    write_head(writer, marker::MAJOR_MAP, len)
+}
+
+/// Write a semantic tag head (major type 6); the caller is responsible for
+/// writing the tagged item afterwards.
+pub fn tag_head<W: Write + ?Sized>(writer: &mut W, tag: u64) -> Result<(), Error<W>> {
+    write_head(writer, marker::MAJOR_TAG, tag)
+}
+
+/// Encode a `bool` as a major 7 simple value.
+pub fn bool<W: Write + ?Sized>(writer: &mut W, value: bool) -> Result<(), Error<W>> {
+    write_head_raw(writer, marker::MAJOR_SIMPLE, if value {
+        marker::SIMPLE_TRUE
+    } else {
+        marker::SIMPLE_FALSE
+    })
+}
+
+/// Encode CBOR `null`.
+pub fn null<W: Write + ?Sized>(writer: &mut W) -> Result<(), Error<W>> {
+    write_head_raw(writer, marker::MAJOR_SIMPLE, marker::SIMPLE_NULL)
+}
+
+/// Encode CBOR `undefined`.
+pub fn undefined<W: Write + ?Sized>(writer: &mut W) -> Result<(), Error<W>> {
+    write_head_raw(writer, marker::MAJOR_SIMPLE, marker::SIMPLE_UNDEFINED)
+}
+
+/// Encode an `f32` (major 7, additional info 26).
+pub fn f32<W: Write + ?Sized>(writer: &mut W, value: f32) -> Result<(), Error<W>> {
+    write_head_raw(writer, marker::MAJOR_SIMPLE, marker::SIMPLE_F32)?;
+    writer.push(&value.to_be_bytes()).map_err(crate::error::EncodeError::Write)
+}
+
+/// Encode `value` as a half-precision float (major 7, additional info 25)
+/// if that would round-trip back to the same `f32` exactly, writing nothing
+/// and returning `false` otherwise so the caller can fall back to
+/// [`f32`]/[`f64`].
+pub fn try_f16<W: Write + ?Sized>(writer: &mut W, value: f32) -> Result<bool, Error<W>> {
+    match crate::core::float16::from_f32_lossless(value) {
+        Some(bits) => {
+            write_head_raw(writer, marker::MAJOR_SIMPLE, marker::SIMPLE_F16)?;
+            writer.push(&bits.to_be_bytes()).map_err(crate::error::EncodeError::Write)?;
+            Ok(true)
+        },
+        None => Ok(false)
+    }
+}
+
+/// Encode an `f64` (major 7, additional info 27).
+pub fn f64<W: Write + ?Sized>(writer: &mut W, value: f64) -> Result<(), Error<W>> {
+    write_head_raw(writer, marker::MAJOR_SIMPLE, marker::SIMPLE_F64)?;
+    writer.push(&value.to_be_bytes()).map_err(crate::error::EncodeError::Write)
+}
+
+pub use marker::{ MAJOR_ARRAY, MAJOR_MAP, MAJOR_BYTES, MAJOR_TEXT, MAJOR_TAG };
+
+/// Streams an indefinite-length array (major type 4) for producers that
+/// don't know the element count up front.
+///
+/// [`new`](Self::new) writes the `0x9f` head; encode each element by calling
+/// the functions in this module (`u64`, `str`, `array_head`, …) directly on
+/// `self.writer`; [`finish`](Self::finish) writes the `0xff` break and
+/// returns the writer.
+pub struct ArrayStreamEncoder<W> {
+    pub writer: W
+}
+
+impl<W: Write> ArrayStreamEncoder<W> {
+    #[inline]
+    pub fn new(mut writer: W) -> Result<Self, Error<W>> {
+        write_indefinite_head(&mut writer, marker::MAJOR_ARRAY)?;
+        Ok(ArrayStreamEncoder { writer })
+    }
+
+    #[inline]
+    pub fn finish(mut self) -> Result<W, Error<W>> {
+        write_break(&mut self.writer)?;
+        Ok(self.writer)
+    }
+}
+
+/// Streams an indefinite-length map (major type 5) for producers that don't
+/// know the entry count up front.
+///
+/// [`new`](Self::new) writes the `0xbf` head; encode each entry as a key
+/// followed by its value (both via the functions in this module) on
+/// `self.writer`; [`finish`](Self::finish) writes the `0xff` break and
+/// returns the writer.
+pub struct MapStreamEncoder<W> {
+    pub writer: W
+}
+
+impl<W: Write> MapStreamEncoder<W> {
+    #[inline]
+    pub fn new(mut writer: W) -> Result<Self, Error<W>> {
+        write_indefinite_head(&mut writer, marker::MAJOR_MAP)?;
+        Ok(MapStreamEncoder { writer })
+    }
+
+    #[inline]
+    pub fn finish(mut self) -> Result<W, Error<W>> {
+        write_break(&mut self.writer)?;
+        Ok(self.writer)
+    }
+}
+
+/// Streams an indefinite-length byte string (major type 2) as a sequence of
+/// definite-length chunks, for producers that want to emit a large byte
+/// string incrementally without buffering it first.
+pub struct BytesStreamEncoder<W> {
+    writer: W
+}
+
+impl<W: Write> BytesStreamEncoder<W> {
+    #[inline]
+    pub fn new(mut writer: W) -> Result<Self, Error<W>> {
+        write_indefinite_head(&mut writer, marker::MAJOR_BYTES)?;
+        Ok(BytesStreamEncoder { writer })
+    }
+
+    /// Writes `chunk` as one definite-length byte-string chunk.
+    #[inline]
+    pub fn push_chunk(&mut self, chunk: &[u8]) -> Result<(), Error<W>> {
+        bytes(&mut self.writer, chunk)
+    }
+
+    #[inline]
+    pub fn finish(mut self) -> Result<W, Error<W>> {
+        write_break(&mut self.writer)?;
+        Ok(self.writer)
+    }
+}
+
+/// Streams an indefinite-length text string (major type 3) as a sequence of
+/// definite-length chunks, for producers that want to emit a large text
+/// string incrementally without buffering it first.
+pub struct TextStreamEncoder<W> {
+    writer: W
+}
+
+impl<W: Write> TextStreamEncoder<W> {
+    #[inline]
+    pub fn new(mut writer: W) -> Result<Self, Error<W>> {
+        write_indefinite_head(&mut writer, marker::MAJOR_TEXT)?;
+        Ok(TextStreamEncoder { writer })
+    }
+
+    /// Writes `chunk` as one definite-length text-string chunk.
+    #[inline]
+    pub fn push_chunk(&mut self, chunk: &str) -> Result<(), Error<W>> {
+        str(&mut self.writer, chunk)
+    }
+
+    #[inline]
+    pub fn finish(mut self) -> Result<W, Error<W>> {
+        write_break(&mut self.writer)?;
+        Ok(self.writer)
+    }
+}
@@ -0,0 +1,96 @@
+//! IEEE 754 binary16 (half-precision) <-> binary32 bit conversion, shared by
+//! [`enc`](super::enc)'s optional half-float encoding and the serde
+//! `Deserializer`'s decoding of major 7 additional info 25.
+
+/// Expand half-precision bits to the `f32` they represent (sign, infinities,
+/// NaNs, normals, and subnormals all handled per IEEE 754; exponent bias 15,
+/// 10-bit mantissa).
+pub(crate) fn to_f32(bits: u16) -> f32 {
+    let bits = bits as u32;
+    let sign = (bits & 0x8000) << 16;
+    let exp = (bits >> 10) & 0x1f;
+    let mant = bits & 0x3ff;
+
+    let bits32 = if exp == 0 {
+        if mant == 0 {
+            // +-0
+            0
+        } else {
+            // subnormal half: normalize the mantissa into a binary32 normal
+            let mut e = -1i32;
+            let mut m = mant;
+            while m & 0x400 == 0 {
+                m <<= 1;
+                e += 1;
+            }
+            m &= 0x3ff;
+            let exp32 = (127 - 15 - e) as u32;
+            (exp32 << 23) | (m << 13)
+        }
+    } else if exp == 0x1f {
+        // infinity or NaN
+        0x7f80_0000 | (mant << 13)
+    } else {
+        let exp32 = exp + (127 - 15);
+        (exp32 << 23) | (mant << 13)
+    };
+
+    f32::from_bits(sign | bits32)
+}
+
+/// Narrow `value` to half-precision bits, but only if doing so would
+/// round-trip back to the same `f32` exactly (values too large become
+/// infinity and are rejected, since that isn't a lossless conversion).
+/// NaN is the exception: any NaN narrows to the quiet NaN `0x7e00`, so its
+/// sign and payload are not preserved. Doesn't need to round correctly in the general case: any mismatch is
+/// caught by the round-trip check below, so the caller never sees a bad
+/// answer.
+pub(crate) fn from_f32_lossless(value: f32) -> Option<u16> {
+    if value.is_nan() {
+        // sign and payload are dropped: every NaN becomes the quiet NaN
+        return Some(0x7e00);
+    }
+
+    let bits32 = value.to_bits();
+    let sign = ((bits32 >> 16) & 0x8000) as u16;
+    let exp32 = ((bits32 >> 23) & 0xff) as i32;
+    let mant32 = bits32 & 0x7f_ffff;
+
+    let bits16 = if exp32 == 0xff {
+        // infinity (mant32 == 0, since NaN already returned above)
+        0x7c00
+    } else if exp32 == 0 && mant32 == 0 {
+        // +-0
+        0
+    } else {
+        let exp16 = exp32 - 127 + 15;
+
+        if exp16 >= 0x1f {
+            // overflows half's exponent range; not lossless
+            return None;
+        } else if exp16 <= 0 {
+            // would be subnormal (or zero) in half precision
+            let shift = 14 - exp16;
+            if shift > 24 {
+                // underflows to zero; not lossless unless value is exactly
+                // zero, which was already handled above
+                return None;
+            }
+            let mant_full = mant32 | 0x80_0000;
+            if mant_full & ((1u32 << shift) - 1) != 0 {
+                // low bits would be lost
+                return None;
+            }
+            (mant_full >> shift) as u16
+        } else {
+            if mant32 & 0x1fff != 0 {
+                // low 13 mantissa bits would be lost
+                return None;
+            }
+            (((exp16 as u32) << 10) | (mant32 >> 13)) as u16
+        }
+    };
+
+    let candidate = sign | bits16;
+    (to_f32(candidate) == value).then_some(candidate)
+}
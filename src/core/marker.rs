@@ -0,0 +1,54 @@
+//! Major type constants and the decoded-head representation shared by
+//! [`enc`](super::enc) and [`dec`](super::dec).
+
+pub const MAJOR_UINT: u8 = 0;
+pub const MAJOR_NINT: u8 = 1;
+pub const MAJOR_BYTES: u8 = 2;
+pub const MAJOR_TEXT: u8 = 3;
+pub const MAJOR_ARRAY: u8 = 4;
+pub const MAJOR_MAP: u8 = 5;
+pub const MAJOR_TAG: u8 = 6;
+pub const MAJOR_SIMPLE: u8 = 7;
+
+pub const INFO_U8: u8 = 24;
+pub const INFO_U16: u8 = 25;
+pub const INFO_U32: u8 = 26;
+pub const INFO_U64: u8 = 27;
+pub const INFO_INDEFINITE: u8 = 31;
+
+pub const SIMPLE_FALSE: u8 = 20;
+pub const SIMPLE_TRUE: u8 = 21;
+pub const SIMPLE_NULL: u8 = 22;
+pub const SIMPLE_UNDEFINED: u8 = 23;
+pub const SIMPLE_F16: u8 = 25;
+pub const SIMPLE_F32: u8 = 26;
+pub const SIMPLE_F64: u8 = 27;
+
+pub const BREAK: u8 = 0xff;
+
+/// A decoded head: the major type plus its argument.
+///
+/// `Arg::Indefinite` is only produced for the bytes/text/array/map majors,
+/// when the low five bits of the initial byte are `31`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Head {
+    pub major: u8,
+    pub arg: Arg
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arg {
+    Len(u64),
+    Indefinite
+}
+
+impl Arg {
+    /// The definite-length argument, or `None` if this is `Indefinite`.
+    #[inline]
+    pub fn as_len(self) -> Option<u64> {
+        match self {
+            Arg::Len(len) => Some(len),
+            Arg::Indefinite => None
+        }
+    }
+}
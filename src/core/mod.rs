@@ -0,0 +1,18 @@
+//! The serde-independent CBOR codec: byte-level encoding and decoding
+//! primitives that the [`serde`](crate::serde) layer is built on top of.
+//!
+//! Everything here works without an allocator; higher level conveniences
+//! (like [`Value`]) additionally require the `use_alloc` feature.
+
+pub(crate) mod marker;
+pub(crate) mod float16;
+pub mod enc;
+pub mod dec;
+
+#[cfg(feature = "use_alloc")]
+mod value;
+
+#[cfg(feature = "use_alloc")]
+pub use value::Value;
+
+pub use marker::{ Head, Arg };
@@ -0,0 +1,167 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A dynamically typed CBOR value.
+///
+/// This is an escape hatch for data whose shape isn't known up front, or
+/// for round-tripping arbitrary CBOR (including constructs, like semantic
+/// tags, that don't map onto a fixed Rust type). Requires the `use_alloc`
+/// feature; (de)serializing it through serde additionally requires
+/// `serde1`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Value {
+    Integer(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Bool(bool),
+    Null,
+    Float(f64),
+
+    /// A semantic tag (major type 6, see [`crate::serde::Tag`]) paired with
+    /// the value it annotates.
+    Tag(u64, Box<Value>)
+}
+
+#[cfg(feature = "serde1")]
+mod impl_serde {
+    use super::Value;
+    use alloc::boxed::Box;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::fmt;
+    use core::marker::PhantomData;
+    use serde::{ Serialize, Serializer, Deserialize, Deserializer };
+    use serde::ser::SerializeMap;
+    use serde::de::{ self, Visitor, MapAccess, SeqAccess, EnumAccess, VariantAccess };
+    use crate::serde::Tag;
+    use crate::serde::de::VALUE_NEWTYPE_NAME;
+
+    impl Serialize for Value {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                Value::Integer(v) => serializer.serialize_i128(*v),
+                Value::Bytes(v) => serializer.serialize_bytes(v),
+                Value::Text(v) => serializer.serialize_str(v),
+                Value::Array(v) => v.serialize(serializer),
+                Value::Map(v) => {
+                    let mut map = serializer.serialize_map(Some(v.len()))?;
+                    for (key, value) in v {
+                        map.serialize_entry(key, value)?;
+                    }
+                    map.end()
+                },
+                Value::Bool(v) => serializer.serialize_bool(*v),
+                Value::Null => serializer.serialize_unit(),
+                Value::Float(v) => serializer.serialize_f64(*v),
+                Value::Tag(tag, value) => Tag { tag: *tag, value: value.as_ref() }.serialize(serializer)
+            }
+        }
+    }
+
+    // A seq/map's size hint comes straight from the (possibly adversarial)
+    // length header on the wire, so it must not be trusted for a raw
+    // preallocation the way serde's derived impls trust it internally (they
+    // clamp it the same way via their own private `size_hint::cautious`).
+    fn size_hint_cautious<T>(hint: Option<usize>) -> usize {
+        const MAX_PREALLOC_BYTES: usize = 4096;
+        hint.map_or(0, |len| len.min(MAX_PREALLOC_BYTES / core::mem::size_of::<T>().max(1)))
+    }
+
+    struct ValueVisitor;
+
+    impl<'de> Visitor<'de> for ValueVisitor {
+        type Value = Value;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("any valid CBOR value")
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+            Ok(Value::Bool(v))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+            Ok(Value::Integer(v.into()))
+        }
+
+        fn visit_i128<E: de::Error>(self, v: i128) -> Result<Value, E> {
+            Ok(Value::Integer(v))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+            Ok(Value::Integer(v.into()))
+        }
+
+        fn visit_u128<E: de::Error>(self, v: u128) -> Result<Value, E> {
+            Ok(Value::Integer(v as i128))
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+            Ok(Value::Float(v))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+            Ok(Value::Text(v.into()))
+        }
+
+        fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+            Ok(Value::Text(v))
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Value, E> {
+            Ok(Value::Bytes(v.into()))
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Value, E> {
+            Ok(Value::Bytes(v))
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+            Ok(Value::Null)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+            Ok(Value::Null)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+            Deserialize::deserialize(deserializer)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+            let mut vec = Vec::with_capacity(size_hint_cautious::<Value>(seq.size_hint()));
+            while let Some(value) = seq.next_element()? {
+                vec.push(value);
+            }
+            Ok(Value::Array(vec))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+            let mut vec = Vec::with_capacity(size_hint_cautious::<(Value, Value)>(map.size_hint()));
+            while let Some(entry) = map.next_entry()? {
+                vec.push(entry);
+            }
+            Ok(Value::Map(vec))
+        }
+
+        // Reached only through the probe in `Value`'s own `Deserialize`
+        // impl below, when the next item is a semantic tag; never through
+        // plain `deserialize_any` (which skips tags transparently for
+        // every other type).
+        fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Value, A::Error> {
+            let (tag, variant) = data.variant_seed(PhantomData::<u64>)?;
+            let value = variant.newtype_variant::<Value>()?;
+            Ok(Value::Tag(tag, Box::new(value)))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Value {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+            deserializer.deserialize_newtype_struct(VALUE_NEWTYPE_NAME, ValueVisitor)
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Error types shared by the core codec and the serde layer.
+
+use core::fmt;
+
+#[cfg(feature = "use_alloc")]
+use alloc::string::String;
+
+/// Errors that can occur while encoding a value.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EncodeError<E> {
+    /// The underlying [`Write`](crate::core::enc::Write) implementation failed.
+    Write(E),
+
+    /// A custom error raised by the serde layer (for example via
+    /// `serde::ser::Error::custom`).
+    #[cfg(feature = "use_alloc")]
+    Msg(String)
+}
+
+impl<E: fmt::Display> fmt::Display for EncodeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Write(err) => write!(f, "write error: {}", err),
+            #[cfg(feature = "use_alloc")]
+            EncodeError::Msg(msg) => f.write_str(msg)
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for EncodeError<E> {}
+
+/// Errors that can occur while decoding a value.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DecodeError<E> {
+    /// The underlying [`Read`](crate::core::dec::Read) implementation failed.
+    Read(E),
+
+    /// The input ended before the value was fully decoded.
+    Eof {
+        name: &'static str,
+        expect: usize
+    },
+
+    /// A major type/marker was found where it is not a valid encoding of
+    /// `name`.
+    Mismatch {
+        name: &'static str,
+        found: u8
+    },
+
+    /// The decoded value does not fit into the requested type (for example
+    /// an integer that overflows `u8`).
+    CastOverflow {
+        name: &'static str
+    },
+
+    /// The marker byte does not correspond to any known CBOR encoding.
+    Unsupported {
+        marker: u8
+    },
+
+    /// There was more data in the input than was consumed decoding the
+    /// value.
+    TrailingData,
+
+    /// Nested arrays/maps/tags went deeper than the decoder's configured
+    /// limit (see [`crate::serde::Deserializer::with_max_depth`]). Guards
+    /// against stack overflow from maliciously (or accidentally) deeply
+    /// nested input.
+    DepthLimitExceeded,
+
+    /// A custom error raised by the serde layer (for example via
+    /// `serde::de::Error::custom`).
+    #[cfg(feature = "use_alloc")]
+    Msg(String)
+}
+
+impl<E: fmt::Display> fmt::Display for DecodeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Read(err) => write!(f, "read error: {}", err),
+            DecodeError::Eof { name, expect } =>
+                write!(f, "eof while decoding {}, want {} more byte(s)", name, expect),
+            DecodeError::Mismatch { name, found } =>
+                write!(f, "type mismatch, expect {}, found marker 0x{:02x}", name, found),
+            DecodeError::CastOverflow { name } =>
+                write!(f, "{} does not fit in the requested type", name),
+            DecodeError::Unsupported { marker } =>
+                write!(f, "unsupported marker 0x{:02x}", marker),
+            DecodeError::TrailingData =>
+                f.write_str("trailing data after value"),
+            DecodeError::DepthLimitExceeded =>
+                f.write_str("nesting depth limit exceeded"),
+            #[cfg(feature = "use_alloc")]
+            DecodeError::Msg(msg) => f.write_str(msg)
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for DecodeError<E> {}
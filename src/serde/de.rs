@@ -0,0 +1,741 @@
+//! The serde [`Deserializer`] implementation.
+
+use serde::de::{ self, Visitor, SeqAccess, MapAccess, EnumAccess, VariantAccess };
+use crate::core::dec::{ self, Read, Reference };
+use crate::core::marker::{ self, Head, Arg };
+use crate::error::DecodeError;
+use crate::serde::ser::TAG_NEWTYPE_NAME;
+
+/// Reserved newtype-struct name [`crate::core::Value`] probes with, to
+/// distinguish a leading semantic tag (captured as `Value::Tag`) from an
+/// untagged value; see the [`deserialize_newtype_struct`](
+/// de::Deserializer::deserialize_newtype_struct) branch below. Unlike
+/// [`TAG_NEWTYPE_NAME`], this never appears on the wire — it only steers
+/// which code path `Value::deserialize` takes.
+pub(crate) const VALUE_NEWTYPE_NAME: &str = "\0cbor4ii::Value";
+
+/// Default limit on array/map/tag nesting, used unless overridden with
+/// [`Deserializer::with_max_depth`]. Deep enough for realistic data, shallow
+/// enough that hitting it well before the native call stack does.
+const DEFAULT_MAX_DEPTH: usize = 128;
+
+impl<E: core::fmt::Debug + core::fmt::Display> de::Error for DecodeError<E> {
+    #[cfg(feature = "use_alloc")]
+    fn custom<T: core::fmt::Display>(msg: T) -> Self {
+        DecodeError::Msg(alloc::string::ToString::to_string(&msg))
+    }
+
+    #[cfg(not(feature = "use_alloc"))]
+    fn custom<T: core::fmt::Display>(_msg: T) -> Self {
+        panic!("serde custom error without `use_alloc`")
+    }
+}
+
+/// A serde deserializer that reads CBOR from a [`Read`] source.
+pub struct Deserializer<R> {
+    reader: R,
+    /// Backs multi-byte reads that a single [`Read::fill`] call couldn't
+    /// satisfy in one go (e.g. a reader that only ever hands back a byte or
+    /// two at a time). Unused, and so not allocated, by readers that always
+    /// fill in one shot.
+    #[cfg(feature = "use_alloc")]
+    scratch: alloc::vec::Vec<u8>,
+    /// Current array/map/tag nesting depth; see [`Self::with_max_depth`].
+    depth: usize,
+    max_depth: usize
+}
+
+impl<'de, R: Read<'de>> Deserializer<R> {
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        Deserializer {
+            reader,
+            #[cfg(feature = "use_alloc")]
+            scratch: alloc::vec::Vec::new(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH
+        }
+    }
+
+    /// Overrides the default nesting-depth limit ([`DEFAULT_MAX_DEPTH`]).
+    /// Exceeding it at any point during decoding fails with
+    /// [`DecodeError::DepthLimitExceeded`], rather than risking a stack
+    /// overflow on maliciously (or accidentally) deeply nested input.
+    #[inline]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns `Err` if there is any data left in the input.
+    pub fn end(&mut self) -> Result<(), DecodeError<R::Error>> {
+        if dec::peek_marker(&mut self.reader).is_ok() {
+            return Err(DecodeError::TrailingData);
+        }
+
+        Ok(())
+    }
+
+    /// Runs `f` with the nesting depth incremented by one, failing with
+    /// [`DecodeError::DepthLimitExceeded`] instead if that would exceed
+    /// [`Self::with_max_depth`]'s limit. Used to bound recursion into
+    /// arrays, maps, and tags.
+    fn with_depth<T>(
+        &mut self, f: impl FnOnce(&mut Self) -> Result<T, DecodeError<R::Error>>
+    ) -> Result<T, DecodeError<R::Error>> {
+        if self.depth >= self.max_depth {
+            return Err(DecodeError::DepthLimitExceeded);
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Read exactly `N` bytes, looping over [`Read::fill`] for readers that
+    /// don't hand back everything in a single call.
+    fn read_array<const N: usize>(&mut self, name: &'static str) -> Result<[u8; N], DecodeError<R::Error>> {
+        let mut out = [0u8; N];
+        let mut filled = 0;
+
+        while filled < N {
+            let chunk = self.reader.fill(N - filled).map_err(DecodeError::Read)?;
+            let buf = chunk.as_slice();
+            if buf.is_empty() {
+                return Err(DecodeError::Eof { name, expect: N - filled });
+            }
+
+            let take = buf.len().min(N - filled);
+            out[filled..filled + take].copy_from_slice(&buf[..take]);
+            self.reader.advance(take);
+            filled += take;
+        }
+
+        Ok(out)
+    }
+
+    /// Slow-path fallback for [`dec::peek_bytes`]: accumulate `len` bytes
+    /// into `self.scratch` by looping over [`Read::fill`], for readers that
+    /// can't satisfy the whole span in one call.
+    #[cfg(feature = "use_alloc")]
+    fn read_slice_buffered(&mut self, len: usize, name: &'static str) -> Result<&[u8], DecodeError<R::Error>> {
+        self.scratch.clear();
+
+        while self.scratch.len() < len {
+            let need = len - self.scratch.len();
+            let chunk = self.reader.fill(need).map_err(DecodeError::Read)?;
+            let buf = chunk.as_slice();
+            if buf.is_empty() {
+                return Err(DecodeError::Eof { name, expect: need });
+            }
+
+            let take = buf.len().min(need);
+            self.scratch.extend_from_slice(&buf[..take]);
+            self.reader.advance(take);
+        }
+
+        Ok(&self.scratch[..])
+    }
+}
+
+macro_rules! forward_deserialize_any {
+    ( $( $f:ident ),* $(,)? ) => {
+        $(
+            #[inline]
+            fn $f<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.deserialize_any(visitor)
+            }
+        )*
+    }
+}
+
+impl<'de, R: Read<'de>> de::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = DecodeError<R::Error>;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let head = dec::read_head(&mut self.reader)?;
+
+        match head.major {
+            marker::MAJOR_UINT => {
+                let Arg::Len(v) = head.arg else { unreachable!("uint is never indefinite") };
+                visitor.visit_u64(v)
+            },
+            marker::MAJOR_NINT => {
+                let Arg::Len(v) = head.arg else { unreachable!("nint is never indefinite") };
+                match i64::try_from(v) {
+                    Ok(v) => visitor.visit_i64(-1 - v),
+                    Err(_) => visitor.visit_i128(-1 - i128::from(v))
+                }
+            },
+            marker::MAJOR_BYTES => self.deserialize_bytes_major(head, visitor),
+            marker::MAJOR_TEXT => self.deserialize_text_major(head, visitor),
+            marker::MAJOR_ARRAY => self.deserialize_array(head, visitor),
+            marker::MAJOR_MAP => self.deserialize_map_major(head, visitor),
+            marker::MAJOR_TAG => {
+                let Arg::Len(tag) = head.arg else {
+                    return Err(DecodeError::Unsupported { marker: 0xdb });
+                };
+
+                // Tag 2/3 (RFC 8949 §3.4.3 bignum) round-trips the big
+                // integers `serialize_i128`/`serialize_u128` fall back to
+                // past `i64`/`u64` range; every other tag is transparent to
+                // ordinary deserialization: unless the caller specifically
+                // asked for a `Tag<T>` or a `Value` (handled in
+                // `deserialize_newtype_struct`), skip the tag number and
+                // decode the enclosed item directly.
+                if tag == 2 || tag == 3 {
+                    return self.with_depth(|de| de.deserialize_bignum(tag, visitor));
+                }
+
+                self.with_depth(|de| de::Deserializer::deserialize_any(&mut *de, visitor))
+            },
+            marker::MAJOR_SIMPLE => self.deserialize_simple(head, visitor),
+            _ => Err(DecodeError::Unsupported { marker: (head.major << 5) })
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if dec::peek_marker(&mut self.reader)? == (marker::MAJOR_SIMPLE << 5) | marker::SIMPLE_NULL {
+            self.reader.advance(1);
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        // Matches `serialize_unit`'s choice of a zero-length array (rather
+        // than `null`) so that `()` stays distinguishable from `None`.
+        let marker = dec::peek_marker(&mut self.reader)?;
+        if marker == marker::MAJOR_ARRAY << 5 {
+            self.reader.advance(1);
+            visitor.visit_unit()
+        } else {
+            Err(DecodeError::Mismatch { name: "unit", found: marker })
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self, _name: &'static str, visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self, name: &'static str, visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        if name == TAG_NEWTYPE_NAME {
+            let head = dec::read_head(&mut self.reader)?;
+            if head.major != marker::MAJOR_TAG {
+                return Err(DecodeError::Mismatch { name: "tag", found: head.major << 5 });
+            }
+            let Arg::Len(tag) = head.arg else {
+                return Err(DecodeError::Unsupported { marker: 0xdb });
+            };
+
+            return self.with_depth(|de| visitor.visit_seq(TagSeqAccess { de, tag: Some(tag) }));
+        }
+
+        if name == VALUE_NEWTYPE_NAME {
+            if dec::peek_marker(&mut self.reader)? >> 5 == marker::MAJOR_TAG {
+                let head = dec::read_head(&mut self.reader)?;
+                let Arg::Len(tag) = head.arg else {
+                    return Err(DecodeError::Unsupported { marker: 0xdb });
+                };
+
+                if tag == 2 || tag == 3 {
+                    return self.with_depth(|de| de.deserialize_bignum(tag, visitor));
+                }
+
+                return self.with_depth(|de| visitor.visit_enum(TagEnumAccess { de, tag }));
+            }
+
+            return de::Deserializer::deserialize_any(self, visitor);
+        }
+
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self, _name: &'static str, _variants: &'static [&'static str], visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        let marker = dec::peek_marker(&mut self.reader)?;
+
+        if marker >> 5 == marker::MAJOR_TEXT {
+            // unit variant, encoded bare as its name
+            visitor.visit_enum(UnitVariantAccess { de: self })
+        } else {
+            // newtype/tuple/struct variant, encoded as a single-entry map
+            let head = dec::read_head(&mut self.reader)?;
+            if head.major != marker::MAJOR_MAP || head.arg.as_len() != Some(1) {
+                return Err(DecodeError::Mismatch { name: "enum", found: marker });
+            }
+            self.with_depth(|de| visitor.visit_enum(VariantAccessImpl { de }))
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+
+    forward_deserialize_any! {
+        deserialize_bool,
+        deserialize_i8, deserialize_i16, deserialize_i32, deserialize_i64, deserialize_i128,
+        deserialize_u8, deserialize_u16, deserialize_u32, deserialize_u64, deserialize_u128,
+        deserialize_f32, deserialize_f64,
+        deserialize_char, deserialize_str, deserialize_string,
+        deserialize_bytes, deserialize_byte_buf,
+        deserialize_seq, deserialize_map, deserialize_identifier,
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self, _name: &'static str, _len: usize, visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self, _name: &'static str, _fields: &'static [&'static str], visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_any(visitor)
+    }
+}
+
+fn to_str<E>(bytes: &[u8]) -> Result<&str, DecodeError<E>> {
+    core::str::from_utf8(bytes).map_err(|_| DecodeError::Mismatch { name: "utf-8 text", found: marker::MAJOR_TEXT << 5 })
+}
+
+impl<'de, R: Read<'de>> Deserializer<R> {
+    /// Reconstruct the integer tag 2/3 (RFC 8949 §3.4.3 bignum) encodes:
+    /// `tag` is already consumed, so only the following byte-string head and
+    /// big-endian magnitude remain to be read. This is what
+    /// `serialize_i128`/`serialize_u128` fall back to for values outside
+    /// `i64`/`u64` range, so it's needed to round-trip them back.
+    fn deserialize_bignum<V: Visitor<'de>>(&mut self, tag: u64, visitor: V) -> Result<V::Value, DecodeError<R::Error>> {
+        let head = dec::read_head(&mut self.reader)?;
+        if head.major != marker::MAJOR_BYTES {
+            return Err(DecodeError::Mismatch { name: "bignum", found: head.major << 5 });
+        }
+        let Arg::Len(len) = head.arg else {
+            return Err(DecodeError::Unsupported { marker: 0xdb });
+        };
+        let len = len as usize;
+        if len > 16 {
+            return Err(DecodeError::Unsupported { marker: (marker::MAJOR_TAG << 5) | tag as u8 });
+        }
+
+        let mut buf = [0u8; 16];
+        let mut filled = 0;
+        while filled < len {
+            let chunk = self.reader.fill(len - filled).map_err(DecodeError::Read)?;
+            let data = chunk.as_slice();
+            if data.is_empty() {
+                return Err(DecodeError::Eof { name: "bignum", expect: len - filled });
+            }
+
+            let take = data.len().min(len - filled);
+            buf[16 - len + filled..16 - len + filled + take].copy_from_slice(&data[..take]);
+            self.reader.advance(take);
+            filled += take;
+        }
+
+        let magnitude = u128::from_be_bytes(buf);
+        if tag == 2 {
+            visitor.visit_u128(magnitude)
+        } else {
+            let magnitude = i128::try_from(magnitude)
+                .map_err(|_| DecodeError::Unsupported { marker: (marker::MAJOR_TAG << 5) | 3 })?;
+            visitor.visit_i128(-1 - magnitude)
+        }
+    }
+
+    fn deserialize_bytes_major<V: Visitor<'de>>(&mut self, head: Head, visitor: V) -> Result<V::Value, DecodeError<R::Error>> {
+        match head.arg {
+            Arg::Len(len) => {
+                let len = len as usize;
+                match dec::peek_bytes(&mut self.reader, len) {
+                    Ok(Reference::Long(buf)) => {
+                        self.reader.advance(len);
+                        visitor.visit_borrowed_bytes(buf)
+                    },
+                    Ok(Reference::Short(buf)) => {
+                        let result = visitor.visit_bytes(buf);
+                        self.reader.advance(len);
+                        result
+                    },
+                    #[cfg(feature = "use_alloc")]
+                    Err(DecodeError::Eof { .. }) => {
+                        let buf = self.read_slice_buffered(len, "bytes")?;
+                        visitor.visit_bytes(buf)
+                    },
+                    Err(e) => Err(e)
+                }
+            },
+            Arg::Indefinite => {
+                #[cfg(feature = "use_alloc")]
+                {
+                    let buf = self.collect_indefinite_chunks(marker::MAJOR_BYTES)?;
+                    visitor.visit_byte_buf(buf)
+                }
+
+                #[cfg(not(feature = "use_alloc"))]
+                {
+                    let _ = visitor;
+                    Err(DecodeError::Unsupported { marker: (marker::MAJOR_BYTES << 5) | marker::INFO_INDEFINITE })
+                }
+            }
+        }
+    }
+
+    fn deserialize_text_major<V: Visitor<'de>>(&mut self, head: Head, visitor: V) -> Result<V::Value, DecodeError<R::Error>> {
+        match head.arg {
+            Arg::Len(len) => {
+                let len = len as usize;
+                match dec::peek_bytes(&mut self.reader, len) {
+                    Ok(Reference::Long(buf)) => {
+                        self.reader.advance(len);
+                        to_str(buf).and_then(|s| visitor.visit_borrowed_str(s))
+                    },
+                    Ok(Reference::Short(buf)) => {
+                        let result = to_str(buf).and_then(|s| visitor.visit_str(s));
+                        self.reader.advance(len);
+                        result
+                    },
+                    #[cfg(feature = "use_alloc")]
+                    Err(DecodeError::Eof { .. }) => {
+                        let buf = self.read_slice_buffered(len, "bytes")?;
+                        to_str(buf).and_then(|s| visitor.visit_str(s))
+                    },
+                    Err(e) => Err(e)
+                }
+            },
+            Arg::Indefinite => {
+                #[cfg(feature = "use_alloc")]
+                {
+                    let buf = self.collect_indefinite_chunks(marker::MAJOR_TEXT)?;
+                    let s = alloc::string::String::from_utf8(buf)
+                        .map_err(|_| DecodeError::Mismatch { name: "utf-8 text", found: marker::MAJOR_TEXT << 5 })?;
+                    visitor.visit_string(s)
+                }
+
+                #[cfg(not(feature = "use_alloc"))]
+                {
+                    let _ = visitor;
+                    Err(DecodeError::Unsupported { marker: (marker::MAJOR_TEXT << 5) | marker::INFO_INDEFINITE })
+                }
+            }
+        }
+    }
+
+    /// Concatenate the definite-length chunks of an indefinite-length byte
+    /// or text string (major type `major`) up to the terminating break.
+    #[cfg(feature = "use_alloc")]
+    fn collect_indefinite_chunks(&mut self, major: u8) -> Result<alloc::vec::Vec<u8>, DecodeError<R::Error>> {
+        let mut buf = alloc::vec::Vec::new();
+
+        loop {
+            if dec::is_break(&mut self.reader)? {
+                dec::read_break(&mut self.reader)?;
+                return Ok(buf);
+            }
+
+            let chunk_head = dec::read_head(&mut self.reader)?;
+            if chunk_head.major != major {
+                return Err(DecodeError::Mismatch { name: "chunk", found: chunk_head.major << 5 });
+            }
+            let Arg::Len(len) = chunk_head.arg else {
+                return Err(DecodeError::Mismatch { name: "definite-length chunk", found: 0xff });
+            };
+
+            let len = len as usize;
+            match dec::peek_bytes(&mut self.reader, len) {
+                Ok(chunk) => {
+                    buf.extend_from_slice(chunk.as_slice());
+                    self.reader.advance(len);
+                },
+                Err(DecodeError::Eof { .. }) => {
+                    buf.extend_from_slice(self.read_slice_buffered(len, "bytes")?);
+                },
+                Err(e) => return Err(e)
+            }
+        }
+    }
+
+    fn deserialize_array<V: Visitor<'de>>(&mut self, head: Head, visitor: V) -> Result<V::Value, DecodeError<R::Error>> {
+        self.with_depth(|de| match head.arg {
+            Arg::Len(len) => visitor.visit_seq(BoundSeqAccess { de, remaining: len }),
+            Arg::Indefinite => visitor.visit_seq(IndefiniteSeqAccess { de })
+        })
+    }
+
+    fn deserialize_map_major<V: Visitor<'de>>(&mut self, head: Head, visitor: V) -> Result<V::Value, DecodeError<R::Error>> {
+        self.with_depth(|de| match head.arg {
+            Arg::Len(len) => visitor.visit_map(BoundMapAccess { de, remaining: len }),
+            Arg::Indefinite => visitor.visit_map(IndefiniteMapAccess { de })
+        })
+    }
+
+    fn deserialize_simple<V: Visitor<'de>>(&mut self, head: Head, visitor: V) -> Result<V::Value, DecodeError<R::Error>> {
+        let Arg::Len(info) = head.arg else {
+            return Err(DecodeError::Unsupported { marker: 0xff });
+        };
+
+        match info as u8 {
+            marker::SIMPLE_FALSE => visitor.visit_bool(false),
+            marker::SIMPLE_TRUE => visitor.visit_bool(true),
+            marker::SIMPLE_NULL | marker::SIMPLE_UNDEFINED => visitor.visit_unit(),
+            marker::SIMPLE_F16 => {
+                let bytes = self.read_array::<2>("f16")?;
+                visitor.visit_f32(crate::core::float16::to_f32(u16::from_be_bytes(bytes)))
+            },
+            marker::SIMPLE_F32 => {
+                let bytes = self.read_array::<4>("f32")?;
+                visitor.visit_f32(f32::from_be_bytes(bytes))
+            },
+            marker::SIMPLE_F64 => {
+                let bytes = self.read_array::<8>("f64")?;
+                visitor.visit_f64(f64::from_be_bytes(bytes))
+            },
+            other => Err(DecodeError::Unsupported { marker: (marker::MAJOR_SIMPLE << 5) | other })
+        }
+    }
+}
+
+struct BoundSeqAccess<'a, R> {
+    de: &'a mut Deserializer<R>,
+    remaining: u64
+}
+
+impl<'de, 'a, R: Read<'de>> SeqAccess<'de> for BoundSeqAccess<'a, R> {
+    type Error = DecodeError<R::Error>;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        usize::try_from(self.remaining).ok()
+    }
+}
+
+struct IndefiniteSeqAccess<'a, R> {
+    de: &'a mut Deserializer<R>
+}
+
+impl<'de, 'a, R: Read<'de>> SeqAccess<'de> for IndefiniteSeqAccess<'a, R> {
+    type Error = DecodeError<R::Error>;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        if dec::is_break(&mut self.de.reader)? {
+            dec::read_break(&mut self.de.reader)?;
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+struct BoundMapAccess<'a, R> {
+    de: &'a mut Deserializer<R>,
+    remaining: u64
+}
+
+impl<'de, 'a, R: Read<'de>> MapAccess<'de> for BoundMapAccess<'a, R> {
+    type Error = DecodeError<R::Error>;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        usize::try_from(self.remaining).ok()
+    }
+}
+
+struct IndefiniteMapAccess<'a, R> {
+    de: &'a mut Deserializer<R>
+}
+
+impl<'de, 'a, R: Read<'de>> MapAccess<'de> for IndefiniteMapAccess<'a, R> {
+    type Error = DecodeError<R::Error>;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        if dec::is_break(&mut self.de.reader)? {
+            dec::read_break(&mut self.de.reader)?;
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+struct TagSeqAccess<'a, R> {
+    de: &'a mut Deserializer<R>,
+    tag: Option<u64>
+}
+
+impl<'de, 'a, R: Read<'de>> SeqAccess<'de> for TagSeqAccess<'a, R> {
+    type Error = DecodeError<R::Error>;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        match self.tag.take() {
+            Some(tag) => seed.deserialize(TagNumberDeserializer(tag, core::marker::PhantomData)).map(Some),
+            None => seed.deserialize(&mut *self.de).map(Some)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(2)
+    }
+}
+
+/// Hands the `(tag, value)` pair captured while decoding a semantic tag to
+/// [`Value::deserialize`](crate::core::Value)'s `visit_enum`: `variant_seed`
+/// decodes the tag number via [`TagNumberDeserializer`], and
+/// `newtype_variant_seed` recurses back into the deserializer for the
+/// tagged item itself.
+struct TagEnumAccess<'a, R> {
+    de: &'a mut Deserializer<R>,
+    tag: u64
+}
+
+impl<'de, 'a, R: Read<'de>> EnumAccess<'de> for TagEnumAccess<'a, R> {
+    type Error = DecodeError<R::Error>;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let tag = self.tag;
+        seed.deserialize(TagNumberDeserializer(tag, core::marker::PhantomData)).map(|v| (v, self))
+    }
+}
+
+impl<'de, 'a, R: Read<'de>> VariantAccess<'de> for TagEnumAccess<'a, R> {
+    type Error = DecodeError<R::Error>;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(de::Error::invalid_type(de::Unexpected::Other("tag"), &"a tagged value"))
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Self::Error> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::invalid_type(de::Unexpected::Other("tag"), &"a tagged value"))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self, _fields: &'static [&'static str], _visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::invalid_type(de::Unexpected::Other("tag"), &"a tagged value"))
+    }
+}
+
+/// Hands a captured tag number back to the `u64` field of `(tag, value)`
+/// when decoding a [`crate::serde::Tag`] or [`crate::core::Value::Tag`].
+struct TagNumberDeserializer<E>(u64, core::marker::PhantomData<E>);
+
+impl<'de, E: de::Error> de::Deserializer<'de> for TagNumberDeserializer<E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u64(self.0)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct UnitVariantAccess<'a, R> {
+    de: &'a mut Deserializer<R>
+}
+
+impl<'de, 'a, R: Read<'de>> EnumAccess<'de> for UnitVariantAccess<'a, R> {
+    type Error = DecodeError<R::Error>;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let value = seed.deserialize(&mut *self.de)?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a, R: Read<'de>> VariantAccess<'de> for UnitVariantAccess<'a, R> {
+    type Error = DecodeError<R::Error>;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, _seed: T) -> Result<T::Value, Self::Error> {
+        Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"a newtype variant"))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"a tuple variant"))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self, _fields: &'static [&'static str], _visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"a struct variant"))
+    }
+}
+
+struct VariantAccessImpl<'a, R> {
+    de: &'a mut Deserializer<R>
+}
+
+impl<'de, 'a, R: Read<'de>> EnumAccess<'de> for VariantAccessImpl<'a, R> {
+    type Error = DecodeError<R::Error>;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let value = seed.deserialize(&mut *self.de)?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a, R: Read<'de>> VariantAccess<'de> for VariantAccessImpl<'a, R> {
+    type Error = DecodeError<R::Error>;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        de::Deserialize::deserialize(&mut *self.de)
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Self::Error> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_tuple(&mut *self.de, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self, fields: &'static [&'static str], visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_struct(&mut *self.de, "", fields, visitor)
+    }
+}
@@ -0,0 +1,154 @@
+//! A [`serde`](::serde) data format for CBOR.
+//!
+//! [`to_vec`]/[`from_slice`] are the usual entry points; [`Serializer`] and
+//! [`Deserializer`] are exposed directly for callers that need a custom
+//! [`core::enc::Write`](crate::core::enc::Write)/
+//! [`core::dec::Read`](crate::core::dec::Read) sink or source (streaming
+//! over a socket, a depth-limited reader, and so on).
+
+pub mod ser;
+pub mod de;
+
+pub use ser::Serializer;
+pub use de::Deserializer;
+
+use serde::{ Serialize, Deserialize };
+use crate::core::enc;
+use crate::error::{ EncodeError, DecodeError };
+
+/// Serialize `value` as CBOR, appending to `writer` and returning it.
+///
+/// `writer` is typically `Vec::new()`, but any [`enc::Write`] sink works,
+/// which lets callers reuse a buffer across calls.
+pub fn to_vec<W, T>(writer: W, value: &T) -> Result<W, EncodeError<W::Error>>
+where
+    W: enc::Write,
+    T: Serialize + ?Sized
+{
+    let mut serializer = Serializer::new(writer);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
+
+/// Serialize `value` as canonical CBOR (RFC 8949 §4.2): integers and
+/// lengths use their shortest encoding, every array/map/string is
+/// definite-length, and map keys are sorted by their encoded byte sequence
+/// (bytewise lexicographic, so a key that is a byte-for-byte prefix of
+/// another sorts first). This produces byte-for-byte reproducible output,
+/// which is what content-addressing or signing over CBOR needs.
+///
+/// Requires the `use_alloc` feature, since map keys must be buffered and
+/// sorted before they can be written out.
+#[cfg(feature = "use_alloc")]
+pub fn to_vec_canonical<W, T>(writer: W, value: &T) -> Result<W, EncodeError<W::Error>>
+where
+    W: enc::Write,
+    T: Serialize + ?Sized
+{
+    let mut serializer = Serializer::canonical(writer);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
+
+/// Serialize `value` as CBOR, preferring a half-precision float (2 bytes
+/// plus the head) over `f32`/`f64` for any float that round-trips losslessly
+/// through half precision. Useful for sensor/IoT payloads where floats are
+/// often small or whole-numbered.
+pub fn to_vec_small_floats<W, T>(writer: W, value: &T) -> Result<W, EncodeError<W::Error>>
+where
+    W: enc::Write,
+    T: Serialize + ?Sized
+{
+    let mut serializer = Serializer::small_floats(writer);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
+
+/// Compute the number of bytes `value` would encode to, without actually
+/// allocating a buffer or storing any of the encoded bytes.
+pub fn serialized_size<T>(value: &T) -> Result<usize, EncodeError<core::convert::Infallible>>
+where
+    T: Serialize + ?Sized
+{
+    let mut serializer = Serializer::new(enc::SizeWriter::default());
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner().size)
+}
+
+/// Deserialize a value of type `T` from a complete CBOR byte slice.
+///
+/// Returns an error if there is any trailing data left after `T` is fully
+/// decoded.
+pub fn from_slice<'a, T>(buf: &'a [u8]) -> Result<T, DecodeError<core::convert::Infallible>>
+where
+    T: Deserialize<'a>
+{
+    let mut deserializer = Deserializer::new(crate::util::SliceReader::new(buf));
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// A CBOR semantic tag (major type 6): a `u64` tag number paired with the
+/// value it annotates, e.g. tag 0 for an RFC 3339 date/time string or tag 32
+/// for a URI (see the [IANA CBOR tags
+/// registry](https://www.iana.org/assignments/cbor-tags/cbor-tags.xhtml)).
+///
+/// `Tag` round-trips through ordinary `#[derive(Serialize, Deserialize)]`
+/// types by routing through a reserved newtype-struct name that this
+/// crate's [`Serializer`]/[`Deserializer`] recognize; with any other data
+/// format it (de)serializes as a plain `(tag, value)` tuple.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag<T> {
+    pub tag: u64,
+    pub value: T
+}
+
+impl<T> Tag<T> {
+    #[inline]
+    pub fn new(tag: u64, value: T) -> Self {
+        Tag { tag, value }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Serialize> Serialize for Tag<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(ser::TAG_NEWTYPE_NAME, &(self.tag, &self.value))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tag<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TagVisitor<T>(core::marker::PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for TagVisitor<T> {
+            type Value = Tag<T>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("a CBOR tagged value")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Tag<T>, A::Error> {
+                let tag = seq.next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let value = seq.next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                Ok(Tag { tag, value })
+            }
+
+            // Other data formats hand the newtype's inner `(tag, value)`
+            // tuple over here rather than calling `visit_seq` directly.
+            fn visit_newtype_struct<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Tag<T>, D::Error> {
+                let (tag, value) = <(u64, T)>::deserialize(deserializer)?;
+                Ok(Tag { tag, value })
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(ser::TAG_NEWTYPE_NAME, TagVisitor(core::marker::PhantomData))
+    }
+}
@@ -0,0 +1,918 @@
+//! The serde [`Serializer`] implementation.
+
+use serde::{ Serialize, ser };
+use serde::ser::{
+    SerializeSeq, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    SerializeMap, SerializeStruct, SerializeStructVariant
+};
+use crate::core::enc::{ self, Write };
+use crate::error::EncodeError;
+
+pub(crate) const TAG_NEWTYPE_NAME: &str = "\0cbor4ii::Tag";
+
+/// A serde serializer that writes CBOR directly into a [`Write`] sink.
+pub struct Serializer<W> {
+    writer: W,
+    #[cfg(feature = "use_alloc")]
+    canonical: bool,
+    small_floats: bool
+}
+
+impl<W: Write> Serializer<W> {
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Serializer {
+            writer,
+            #[cfg(feature = "use_alloc")]
+            canonical: false,
+            small_floats: false
+        }
+    }
+
+    /// Like [`Serializer::new`], but produces canonical CBOR (RFC 8949
+    /// §4.2): shortest-form integers/lengths, definite-length containers,
+    /// and map keys sorted by their encoded byte sequence. See
+    /// [`crate::serde::to_vec_canonical`].
+    #[cfg(feature = "use_alloc")]
+    #[inline]
+    pub fn canonical(writer: W) -> Self {
+        Serializer { writer, canonical: true, small_floats: false }
+    }
+
+    /// Like [`Serializer::new`], but `f32`/`f64` values are written as a
+    /// half-precision float whenever that round-trips losslessly, shrinking
+    /// payloads of small or whole-numbered floats at the cost of a few
+    /// extra cycles per float encoded.
+    #[inline]
+    pub fn small_floats(writer: W) -> Self {
+        Serializer {
+            writer,
+            #[cfg(feature = "use_alloc")]
+            canonical: false,
+            small_floats: true
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    #[cfg(feature = "use_alloc")]
+    #[inline]
+    fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> ser::Error for EncodeError<E> {
+    #[cfg(feature = "use_alloc")]
+    fn custom<T: core::fmt::Display>(msg: T) -> Self {
+        EncodeError::Msg(alloc::string::ToString::to_string(&msg))
+    }
+
+    #[cfg(not(feature = "use_alloc"))]
+    fn custom<T: core::fmt::Display>(_msg: T) -> Self {
+        panic!("serde custom error without `use_alloc`")
+    }
+}
+
+macro_rules! forward_int {
+    ( $( $f:ident : $ty:ty => $cast:ty ),* $(,)? ) => {
+        $(
+            #[inline]
+            fn $f(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                enc::i64(&mut self.writer, v as $cast)
+            }
+        )*
+    }
+}
+
+macro_rules! forward_uint {
+    ( $( $f:ident : $ty:ty ),* $(,)? ) => {
+        $(
+            #[inline]
+            fn $f(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                enc::u64(&mut self.writer, v as u64)
+            }
+        )*
+    }
+}
+
+impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    type SerializeSeq = CollectionSerializer<'a, W>;
+    type SerializeTuple = CollectionSerializer<'a, W>;
+    type SerializeTupleStruct = CollectionSerializer<'a, W>;
+    type SerializeTupleVariant = CollectionSerializer<'a, W>;
+    type SerializeMap = MapSerializer<'a, W>;
+    type SerializeStruct = MapSerializer<'a, W>;
+    type SerializeStructVariant = MapSerializer<'a, W>;
+
+    forward_int! {
+        serialize_i8: i8 => i64,
+        serialize_i16: i16 => i64,
+        serialize_i32: i32 => i64,
+        serialize_i64: i64 => i64,
+    }
+
+    forward_uint! {
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        if let Ok(v) = i64::try_from(v) {
+            enc::i64(&mut self.writer, v)
+        } else if let Ok(v) = u64::try_from(v) {
+            enc::u64(&mut self.writer, v)
+        } else {
+            serialize_bigint(&mut self.writer, v)
+        }
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        if let Ok(v) = u64::try_from(v) {
+            enc::u64(&mut self.writer, v)
+        } else {
+            // `v` can exceed `i128::MAX`, so go through the magnitude
+            // directly rather than a lossy `v as i128` cast.
+            serialize_bigint_magnitude(&mut self.writer, 2, v)
+        }
+    }
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        enc::bool(&mut self.writer, v)
+    }
+
+    #[inline]
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        if self.small_floats && enc::try_f16(&mut self.writer, v)? {
+            return Ok(());
+        }
+        enc::f32(&mut self.writer, v)
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        if self.small_floats {
+            let as_f32 = v as f32;
+            if as_f32 as f64 == v && enc::try_f16(&mut self.writer, as_f32)? {
+                return Ok(());
+            }
+        }
+        enc::f64(&mut self.writer, v)
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        let mut buf = [0; 4];
+        enc::str(&mut self.writer, v.encode_utf8(&mut buf))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        enc::str(&mut self.writer, v)
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        enc::bytes(&mut self.writer, v)
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        enc::null(&mut self.writer)
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    // Encoded as a zero-length array rather than `null` so that `()` stays
+    // distinguishable from `Option::None` (both of which would otherwise
+    // collapse onto the same CBOR simple value).
+    #[inline]
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        enc::array_head(&mut self.writer, 0)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        enc::array_head(&mut self.writer, 0)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str
+    ) -> Result<Self::Ok, Self::Error> {
+        enc::str(&mut self.writer, variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T
+    ) -> Result<Self::Ok, Self::Error> {
+        if name == TAG_NEWTYPE_NAME {
+            return value.serialize(TagSerializer(self));
+        }
+
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T
+    ) -> Result<Self::Ok, Self::Error> {
+        enc::map_head(&mut self.writer, 1)?;
+        enc::str(&mut self.writer, variant)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        if self.is_canonical() && len.is_none() {
+            // The element count isn't known up front, but canonical CBOR
+            // requires a definite-length head, so buffer the encoded
+            // elements until `end()` learns how many there were.
+            return Ok(CollectionSerializer { ser: self, indefinite: false, canonical_buf: Some((alloc::vec::Vec::new(), 0)) });
+        }
+
+        let indefinite = match len {
+            Some(len) => { enc::array_head(&mut self.writer, len as u64)?; false },
+            None => { enc::write_indefinite_head(&mut self.writer, enc::MAJOR_ARRAY)?; true }
+        };
+        Ok(CollectionSerializer { ser: self, indefinite, #[cfg(feature = "use_alloc")] canonical_buf: None })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        enc::array_head(&mut self.writer, len as u64)?;
+        Ok(CollectionSerializer { ser: self, indefinite: false, #[cfg(feature = "use_alloc")] canonical_buf: None })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        enc::array_head(&mut self.writer, len as u64)?;
+        Ok(CollectionSerializer { ser: self, indefinite: false, #[cfg(feature = "use_alloc")] canonical_buf: None })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        enc::map_head(&mut self.writer, 1)?;
+        enc::str(&mut self.writer, variant)?;
+        enc::array_head(&mut self.writer, len as u64)?;
+        Ok(CollectionSerializer { ser: self, indefinite: false, #[cfg(feature = "use_alloc")] canonical_buf: None })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        if self.is_canonical() {
+            return Ok(MapSerializer {
+                ser: self,
+                indefinite: false,
+                canonical: Some(alloc::vec::Vec::with_capacity(len.unwrap_or(0))),
+                pending_key: None
+            });
+        }
+
+        let indefinite = match len {
+            Some(len) => { enc::map_head(&mut self.writer, len as u64)?; false },
+            None => { enc::write_indefinite_head(&mut self.writer, enc::MAJOR_MAP)?; true }
+        };
+        Ok(MapSerializer {
+            ser: self,
+            indefinite,
+            #[cfg(feature = "use_alloc")] canonical: None,
+            #[cfg(feature = "use_alloc")] pending_key: None
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        if self.is_canonical() {
+            return Ok(MapSerializer {
+                ser: self,
+                indefinite: false,
+                canonical: Some(alloc::vec::Vec::with_capacity(len)),
+                pending_key: None
+            });
+        }
+
+        enc::map_head(&mut self.writer, len as u64)?;
+        Ok(MapSerializer {
+            ser: self,
+            indefinite: false,
+            #[cfg(feature = "use_alloc")] canonical: None,
+            #[cfg(feature = "use_alloc")] pending_key: None
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        enc::map_head(&mut self.writer, 1)?;
+        enc::str(&mut self.writer, variant)?;
+
+        #[cfg(feature = "use_alloc")]
+        if self.is_canonical() {
+            return Ok(MapSerializer {
+                ser: self,
+                indefinite: false,
+                canonical: Some(alloc::vec::Vec::with_capacity(len)),
+                pending_key: None
+            });
+        }
+
+        enc::map_head(&mut self.writer, len as u64)?;
+        Ok(MapSerializer {
+            ser: self,
+            indefinite: false,
+            #[cfg(feature = "use_alloc")] canonical: None,
+            #[cfg(feature = "use_alloc")] pending_key: None
+        })
+    }
+
+    fn collect_str<T: core::fmt::Display + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        {
+            let s = alloc::string::ToString::to_string(value);
+            self.serialize_str(&s)
+        }
+
+        #[cfg(not(feature = "use_alloc"))]
+        {
+            let _ = value;
+            Err(<Self::Error as ser::Error>::custom("collect_str requires `use_alloc`"))
+        }
+    }
+}
+
+/// A buffered canonical sub-encode always uses `Vec<u8>` as its sink, whose
+/// `Write::Error` is `Infallible`; re-home that into the caller's own error
+/// type (a custom `EncodeError::Msg` raised mid-value still needs to
+/// propagate, but a `Write` failure can't happen against a `Vec`).
+#[cfg(feature = "use_alloc")]
+fn rehome_canonical_err<E>(err: EncodeError<core::convert::Infallible>) -> EncodeError<E> {
+    match err {
+        EncodeError::Write(never) => match never {},
+        EncodeError::Msg(msg) => EncodeError::Msg(msg)
+    }
+}
+
+fn serialize_bigint_magnitude<W: Write>(writer: &mut W, tag: u64, magnitude: u128) -> Result<(), EncodeError<W::Error>> {
+    let bytes = magnitude.to_be_bytes();
+    let first_nonzero = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len() - 1);
+
+    enc::tag_head(writer, tag)?;
+    enc::bytes(writer, &bytes[first_nonzero..])
+}
+
+fn serialize_bigint<W: Write>(writer: &mut W, value: i128) -> Result<(), EncodeError<W::Error>> {
+    let (tag, magnitude) = if value >= 0 {
+        (2, value as u128)
+    } else {
+        (3, (-1 - value) as u128)
+    };
+
+    serialize_bigint_magnitude(writer, tag, magnitude)
+}
+
+/// A one-shot serializer used to decompose the `(tag, value)` pair that
+/// [`crate::serde::Tag`] feeds through `serialize_newtype_struct`: it only
+/// understands `serialize_tuple(2)`, handing off to
+/// [`TagTupleSerializer`] to write the tag head followed by the value.
+struct TagSerializer<'a, W>(&'a mut Serializer<W>);
+
+macro_rules! tag_serializer_unreachable {
+    ( $( fn $f:ident(self $(, $arg:ident : $ty:ty )*) -> $ret:ty; )* ) => {
+        $(
+            fn $f(self $(, $arg: $ty )*) -> $ret {
+                let _ = ( $( $arg, )* );
+                Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+            }
+        )*
+    }
+}
+
+impl<'a, W: Write> ser::Serializer for TagSerializer<'a, W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    type SerializeSeq = ser::Impossible<(), Self::Error>;
+    type SerializeTuple = TagTupleSerializer<'a, W>;
+    type SerializeTupleStruct = ser::Impossible<(), Self::Error>;
+    type SerializeTupleVariant = ser::Impossible<(), Self::Error>;
+    type SerializeMap = ser::Impossible<(), Self::Error>;
+    type SerializeStruct = ser::Impossible<(), Self::Error>;
+    type SerializeStructVariant = ser::Impossible<(), Self::Error>;
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        if len != 2 {
+            return Err(<Self::Error as ser::Error>::custom("Tag must serialize as a 2-tuple"));
+        }
+
+        Ok(TagTupleSerializer { ser: self.0, index: 0 })
+    }
+
+    tag_serializer_unreachable! {
+        fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error>;
+        fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error>;
+        fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error>;
+        fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error>;
+        fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error>;
+        fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error>;
+        fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error>;
+        fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error>;
+        fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error>;
+        fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error>;
+        fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error>;
+        fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error>;
+        fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error>;
+        fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error>;
+        fn serialize_none(self) -> Result<Self::Ok, Self::Error>;
+        fn serialize_unit(self) -> Result<Self::Ok, Self::Error>;
+        fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error>;
+        fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error>;
+        fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error>;
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        let _ = value;
+        Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+    }
+
+    fn serialize_unit_variant(
+        self, _name: &'static str, _index: u32, _variant: &'static str
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self, _name: &'static str, value: &T
+    ) -> Result<Self::Ok, Self::Error> {
+        let _ = value;
+        Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self, _name: &'static str, _index: u32, _variant: &'static str, value: &T
+    ) -> Result<Self::Ok, Self::Error> {
+        let _ = value;
+        Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self, _name: &'static str, len: usize
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        let _ = len;
+        Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+    }
+
+    fn serialize_tuple_variant(
+        self, _name: &'static str, _index: u32, _variant: &'static str, len: usize
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let _ = len;
+        Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+    }
+
+    fn serialize_struct(
+        self, _name: &'static str, len: usize
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        let _ = len;
+        Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+    }
+
+    fn serialize_struct_variant(
+        self, _name: &'static str, _index: u32, _variant: &'static str, len: usize
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let _ = len;
+        Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+    }
+
+    fn collect_str<T: core::fmt::Display + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        let _ = value;
+        Err(<Self::Error as ser::Error>::custom("expected a (tag, value) tuple"))
+    }
+}
+
+/// Writes the tag head from the first tuple element (captured via
+/// [`TagNumberSerializer`]), then the value from the second.
+struct TagTupleSerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
+    index: u8
+}
+
+impl<'a, W: Write> SerializeTuple for TagTupleSerializer<'a, W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if self.index == 0 {
+            let tag = value.serialize(TagNumberSerializer(core::marker::PhantomData::<W>))?;
+            enc::tag_head(&mut self.ser.writer, tag)?;
+        } else {
+            value.serialize(&mut *self.ser)?;
+        }
+
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+/// Accepts only an unsigned integer, returning it as the tag number; used
+/// for the first element of the `(tag, value)` pair.
+struct TagNumberSerializer<W>(core::marker::PhantomData<W>);
+
+macro_rules! tag_number_unreachable {
+    ( $( fn $f:ident(self $(, $arg:ident : $ty:ty )*) -> $ret:ty; )* ) => {
+        $(
+            fn $f(self $(, $arg: $ty )*) -> $ret {
+                let _ = ( $( $arg, )* );
+                Err(<Self::Error as ser::Error>::custom("tag number must be an unsigned integer"))
+            }
+        )*
+    }
+}
+
+impl<W: Write> ser::Serializer for TagNumberSerializer<W> {
+    type Ok = u64;
+    type Error = EncodeError<W::Error>;
+
+    type SerializeSeq = ser::Impossible<u64, Self::Error>;
+    type SerializeTuple = ser::Impossible<u64, Self::Error>;
+    type SerializeTupleStruct = ser::Impossible<u64, Self::Error>;
+    type SerializeTupleVariant = ser::Impossible<u64, Self::Error>;
+    type SerializeMap = ser::Impossible<u64, Self::Error>;
+    type SerializeStruct = ser::Impossible<u64, Self::Error>;
+    type SerializeStructVariant = ser::Impossible<u64, Self::Error>;
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        Ok(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> { Ok(v.into()) }
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> { Ok(v.into()) }
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> { Ok(v.into()) }
+
+    tag_number_unreachable! {
+        fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error>;
+        fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error>;
+        fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error>;
+        fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error>;
+        fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error>;
+        fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error>;
+        fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error>;
+        fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error>;
+        fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error>;
+        fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error>;
+        fn serialize_none(self) -> Result<Self::Ok, Self::Error>;
+        fn serialize_unit(self) -> Result<Self::Ok, Self::Error>;
+        fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error>;
+        fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error>;
+        fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error>;
+        fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error>;
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        let _ = value;
+        Err(<Self::Error as ser::Error>::custom("tag number must be an unsigned integer"))
+    }
+
+    fn serialize_unit_variant(
+        self, _name: &'static str, _index: u32, _variant: &'static str
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(<Self::Error as ser::Error>::custom("tag number must be an unsigned integer"))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self, _name: &'static str, value: &T
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self, _name: &'static str, _index: u32, _variant: &'static str, value: &T
+    ) -> Result<Self::Ok, Self::Error> {
+        let _ = value;
+        Err(<Self::Error as ser::Error>::custom("tag number must be an unsigned integer"))
+    }
+
+    fn serialize_tuple_struct(
+        self, _name: &'static str, len: usize
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        let _ = len;
+        Err(<Self::Error as ser::Error>::custom("tag number must be an unsigned integer"))
+    }
+
+    fn serialize_tuple_variant(
+        self, _name: &'static str, _index: u32, _variant: &'static str, len: usize
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let _ = len;
+        Err(<Self::Error as ser::Error>::custom("tag number must be an unsigned integer"))
+    }
+
+    fn serialize_struct(
+        self, _name: &'static str, len: usize
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        let _ = len;
+        Err(<Self::Error as ser::Error>::custom("tag number must be an unsigned integer"))
+    }
+
+    fn serialize_struct_variant(
+        self, _name: &'static str, _index: u32, _variant: &'static str, len: usize
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let _ = len;
+        Err(<Self::Error as ser::Error>::custom("tag number must be an unsigned integer"))
+    }
+
+    fn collect_str<T: core::fmt::Display + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        let _ = value;
+        Err(<Self::Error as ser::Error>::custom("tag number must be an unsigned integer"))
+    }
+}
+
+pub struct CollectionSerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
+    indefinite: bool,
+    /// Set only for a canonical-mode sequence whose length wasn't known up
+    /// front: the encoded elements and a running count, flushed as a
+    /// definite-length head once `end()` learns the total.
+    #[cfg(feature = "use_alloc")]
+    canonical_buf: Option<(alloc::vec::Vec<u8>, u64)>
+}
+
+impl<'a, W: Write> CollectionSerializer<'a, W> {
+    #[cfg(feature = "use_alloc")]
+    fn serialize_canonical_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError<W::Error>> {
+        let (buf, count) = self.canonical_buf.as_mut().expect("canonical_buf must be set");
+        let mut sub = Serializer::canonical(core::mem::take(buf));
+        value.serialize(&mut sub).map_err(rehome_canonical_err)?;
+        *buf = sub.into_inner();
+        *count += 1;
+        Ok(())
+    }
+
+    #[cfg(feature = "use_alloc")]
+    fn end_canonical(self) -> Result<(), EncodeError<W::Error>> {
+        if let Some((buf, count)) = self.canonical_buf {
+            enc::array_head(&mut self.ser.writer, count)?;
+            self.ser.writer.push(&buf).map_err(EncodeError::Write)?;
+            return Ok(());
+        }
+
+        if self.indefinite {
+            enc::write_break(&mut self.ser.writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> SerializeSeq for CollectionSerializer<'a, W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        if self.canonical_buf.is_some() {
+            return self.serialize_canonical_element(value);
+        }
+
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        return self.end_canonical();
+
+        #[cfg(not(feature = "use_alloc"))]
+        {
+            if self.indefinite {
+                enc::write_break(&mut self.ser.writer)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl<'a, W: Write> SerializeTuple for CollectionSerializer<'a, W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        if self.indefinite {
+            enc::write_break(&mut self.ser.writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> SerializeTupleStruct for CollectionSerializer<'a, W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> SerializeTupleVariant for CollectionSerializer<'a, W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+pub struct MapSerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
+    indefinite: bool,
+    /// Set in canonical mode: the `(encoded key, encoded value)` pairs seen
+    /// so far, sorted and flushed by `end()`.
+    #[cfg(feature = "use_alloc")]
+    canonical: Option<alloc::vec::Vec<(alloc::vec::Vec<u8>, alloc::vec::Vec<u8>)>>,
+    /// The pending key's encoded bytes, between `serialize_key` and
+    /// `serialize_value`.
+    #[cfg(feature = "use_alloc")]
+    pending_key: Option<alloc::vec::Vec<u8>>
+}
+
+impl<'a, W: Write> MapSerializer<'a, W> {
+    #[cfg(feature = "use_alloc")]
+    fn encode_canonical<T: Serialize + ?Sized>(value: &T) -> Result<alloc::vec::Vec<u8>, EncodeError<W::Error>> {
+        let mut sub = Serializer::canonical(alloc::vec::Vec::new());
+        value.serialize(&mut sub).map_err(rehome_canonical_err)?;
+        Ok(sub.into_inner())
+    }
+
+    #[cfg(feature = "use_alloc")]
+    fn push_canonical_entry(&mut self, key: alloc::vec::Vec<u8>, value: alloc::vec::Vec<u8>) {
+        self.canonical.as_mut()
+            .expect("push_canonical_entry called outside canonical mode")
+            .push((key, value));
+    }
+
+    #[cfg(feature = "use_alloc")]
+    fn end_canonical(self) -> Result<(), EncodeError<W::Error>> {
+        if let Some(mut entries) = self.canonical {
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            enc::map_head(&mut self.ser.writer, entries.len() as u64)?;
+            for (key, value) in entries {
+                self.ser.writer.push(&key).map_err(EncodeError::Write)?;
+                self.ser.writer.push(&value).map_err(EncodeError::Write)?;
+            }
+            return Ok(());
+        }
+
+        if self.indefinite {
+            enc::write_break(&mut self.ser.writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> SerializeMap for MapSerializer<'a, W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        if self.canonical.is_some() {
+            self.pending_key = Some(Self::encode_canonical(key)?);
+            return Ok(());
+        }
+
+        key.serialize(&mut *self.ser)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        if self.canonical.is_some() {
+            let key = self.pending_key.take().expect("serialize_value called before serialize_key");
+            let value = Self::encode_canonical(value)?;
+            self.push_canonical_entry(key, value);
+            return Ok(());
+        }
+
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        return self.end_canonical();
+
+        #[cfg(not(feature = "use_alloc"))]
+        {
+            if self.indefinite {
+                enc::write_break(&mut self.ser.writer)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl<'a, W: Write> SerializeStruct for MapSerializer<'a, W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T
+    ) -> Result<(), Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        if self.canonical.is_some() {
+            let key = Self::encode_canonical(key)?;
+            let value = Self::encode_canonical(value)?;
+            self.push_canonical_entry(key, value);
+            return Ok(());
+        }
+
+        enc::str(&mut self.ser.writer, key)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        return self.end_canonical();
+
+        #[cfg(not(feature = "use_alloc"))]
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> SerializeStructVariant for MapSerializer<'a, W> {
+    type Ok = ();
+    type Error = EncodeError<W::Error>;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T
+    ) -> Result<(), Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        if self.canonical.is_some() {
+            let key = Self::encode_canonical(key)?;
+            let value = Self::encode_canonical(value)?;
+            self.push_canonical_entry(key, value);
+            return Ok(());
+        }
+
+        enc::str(&mut self.ser.writer, key)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "use_alloc")]
+        return self.end_canonical();
+
+        #[cfg(not(feature = "use_alloc"))]
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+//! Small internal helpers shared by the [`core`](crate::core) and
+//! [`serde`](crate::serde) layers.
+
+#![cfg(feature = "serde1")]
+
+use core::convert::Infallible;
+use crate::core::dec::{ Read, Reference };
+
+/// A [`Read`] implementation over an in-memory slice.
+///
+/// Reading from a slice can never fail, so `advance`/`fill` don't need a
+/// real error type; everything is returned by [`Reference::Long`], fully
+/// zero-copy.
+pub struct SliceReader<'de> {
+    slice: &'de [u8]
+}
+
+impl<'de> SliceReader<'de> {
+    #[inline]
+    pub fn new(slice: &'de [u8]) -> Self {
+        SliceReader { slice }
+    }
+}
+
+impl<'de> Read<'de> for SliceReader<'de> {
+    type Error = Infallible;
+
+    #[inline]
+    fn fill<'b>(&'b mut self, want: usize) -> Result<Reference<'de, 'b>, Self::Error> {
+        Ok(if want <= self.slice.len() {
+            Reference::Long(&self.slice[..want])
+        } else {
+            Reference::Long(self.slice)
+        })
+    }
+
+    #[inline]
+    fn advance(&mut self, n: usize) {
+        let n = core::cmp::min(n, self.slice.len());
+        self.slice = &self.slice[n..];
+    }
+}
@@ -151,6 +151,71 @@ fn test_serde_value() {
         Value::Text("a".into()),
         Value::Bool(false)
     )]));
+    assert_test!(Value::Float(1.5));
+
+    // a half-precision float on the wire also decodes into `Value::Float`
+    let value: Value = cbor4ii::serde::from_slice(&[0xf9, 0x3e, 0x00]).unwrap();
+    assert_eq!(value, Value::Float(1.5));
+
+    // an array claiming ~4 billion elements (but with no data behind it)
+    // must not preallocate that much up front; it should fail cleanly
+    // instead of aborting the process
+    let err = cbor4ii::serde::from_slice::<Value>(&[0x9a, 0xff, 0xff, 0xff, 0xfe]).unwrap_err();
+    assert!(matches!(err, cbor4ii::DecodeError::Eof { .. }));
+}
+
+#[test]
+fn test_serde_tag() {
+    use cbor4ii::serde::Tag;
+
+    assert_test!(Tag::new(0, "2023-01-01T00:00:00Z".to_string()));
+    assert_test!(Tag::new(32, "https://example.com".to_string()));
+    assert_test!(vec![Tag::new(1, 1677771717u64)]);
+
+    // tags are transparent when decoding into an ordinary type
+    let buf = to_vec(Vec::new(), &Tag::new(0, "hello".to_string())).unwrap();
+    let value: String = de(&buf, &"hello".to_string());
+    assert_eq!(value, "hello");
+}
+
+#[test]
+fn test_serde_bignum() {
+    // values beyond u64::MAX/i64::MIN fall back to a tag 2/3 bignum, which
+    // must decode back into the same i128/u128 (not just the in-range case)
+    assert_test!(u128::from(u64::MAX) + 1);
+    assert_test!(u128::MAX);
+    assert_test!(i128::MAX);
+    assert_test!(i128::MIN);
+    assert_test!(i128::MIN + 1);
+    assert_test!(vec![(10u128, 99999i128), (u128::MAX, i128::MIN)]);
+
+    // an i128 beyond i64::MAX but still within u64::MAX fits in a plain
+    // UINT and must not fall back to a (longer) tag 2 bignum
+    let value = 10_000_000_000_000_000_000i128;
+    let buf = to_vec(Vec::new(), &value).unwrap();
+    assert_eq!(buf[0], 0x1b); // UINT, 8-byte argument
+    assert_test!(value);
+}
+
+#[test]
+#[cfg(feature = "serde-value")]
+fn test_serde_value_bignum() {
+    use cbor4ii::core::Value;
+
+    assert_test!(Value::Integer(i128::MAX));
+    assert_test!(Value::Integer(i128::MIN));
+}
+
+#[test]
+#[cfg(feature = "serde-value")]
+fn test_serde_value_tag() {
+    use cbor4ii::core::Value;
+
+    assert_test!(Value::Tag(0, Box::new(Value::Text("2023-01-01T00:00:00Z".into()))));
+    assert_test!(Value::Array(vec![
+        Value::Tag(32, Box::new(Value::Text("https://example.com".into()))),
+        Value::Integer(1)
+    ]));
 }
 
 #[test]
@@ -241,3 +306,228 @@ fn test_serde_skip() {
     assert_eq!(value.b, None);
     assert_eq!(value.c, skipit.c);
 }
+
+/// Decodes any CBOR map, preserving the on-the-wire key order, so tests can
+/// observe how a canonical encoder reordered entries.
+#[derive(Debug, PartialEq)]
+struct OrderedEntries(Vec<(String, i32)>);
+
+impl<'de> Deserialize<'de> for OrderedEntries {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct V;
+
+        impl<'de> serde::de::Visitor<'de> for V {
+            type Value = OrderedEntries;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a map")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<OrderedEntries, A::Error> {
+                let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(OrderedEntries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(V)
+    }
+}
+
+#[test]
+fn test_serde_canonical() {
+    use cbor4ii::serde::to_vec_canonical;
+
+    // encoded key bytes are sorted bytewise, not by insertion order or by
+    // plain string order: a CBOR text string's head byte encodes its
+    // length, so "aa" (head 0x62) sorts after both single-byte-length keys
+    // even though "aa" < "b" as plain strings
+    let mut map = BTreeMap::new();
+    map.insert("b".to_string(), 1);
+    map.insert("aa".to_string(), 2);
+    map.insert("a".to_string(), 3);
+
+    let buf = to_vec_canonical(Vec::new(), &map).unwrap();
+
+    let ordered: OrderedEntries = de(&buf, &OrderedEntries(Vec::new()));
+    assert_eq!(ordered.0, vec![
+        ("a".to_string(), 3),
+        ("b".to_string(), 1),
+        ("aa".to_string(), 2)
+    ]);
+
+    let value: BTreeMap<String, i32> = de(&buf, &map);
+    assert_eq!(value, map);
+
+    // struct fields get the same treatment
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    struct Unsorted {
+        zebra: i32,
+        apple: i32
+    }
+    let unsorted = Unsorted { zebra: 1, apple: 2 };
+    let buf = to_vec_canonical(Vec::new(), &unsorted).unwrap();
+    let ordered: OrderedEntries = de(&buf, &OrderedEntries(Vec::new()));
+    assert_eq!(ordered.0, vec![
+        ("apple".to_string(), 2),
+        ("zebra".to_string(), 1)
+    ]);
+
+    // same input always produces the same output
+    let buf2 = to_vec_canonical(Vec::new(), &unsorted).unwrap();
+    assert_eq!(buf, buf2);
+}
+
+#[test]
+fn test_serde_depth_limit() {
+    use std::convert::Infallible;
+    use cbor4ii::core::dec;
+    use cbor4ii::serde::Deserializer;
+    use cbor4ii::DecodeError;
+
+    // a one-shot reader handing back the whole slice, like `SlowReader` in
+    // `test_serde_cow` but without the byte-at-a-time throttling
+    struct SliceReader<'de>(&'de [u8]);
+
+    impl<'de> dec::Read<'de> for SliceReader<'de> {
+        type Error = Infallible;
+
+        #[inline]
+        fn fill<'b>(&'b mut self, _want: usize) -> Result<dec::Reference<'de, 'b>, Self::Error> {
+            Ok(dec::Reference::Long(self.0))
+        }
+
+        #[inline]
+        fn advance(&mut self, n: usize) {
+            let n = n.min(self.0.len());
+            self.0 = &self.0[n..];
+        }
+    }
+
+    // 200 levels of nested single-element arrays wrapping a `0`
+    let mut buf = vec![0x81u8; 200];
+    buf.push(0x00);
+
+    // the default limit rejects it...
+    let mut deserializer = Deserializer::new(SliceReader(&buf));
+    let err = <Vec<serde::de::IgnoredAny> as Deserialize>::deserialize(&mut deserializer).unwrap_err();
+    assert!(matches!(err, DecodeError::DepthLimitExceeded), "{:?}", err);
+
+    // ...but a deserializer configured with a high enough limit accepts it
+    let mut deserializer = Deserializer::new(SliceReader(&buf)).with_max_depth(300);
+    <Vec<serde::de::IgnoredAny> as Deserialize>::deserialize(&mut deserializer).unwrap();
+}
+
+#[test]
+fn test_serde_serialized_size() {
+    use cbor4ii::serde::serialized_size;
+
+    let value = vec![Some(0x99u32), None, Some(0x33u32)];
+    let size = serialized_size(&value).unwrap();
+    let buf = to_vec(Vec::new(), &value).unwrap();
+    assert_eq!(size, buf.len());
+
+    #[derive(Serialize)]
+    struct Test {
+        name: String,
+        map: BTreeMap<String, i32>
+    }
+    let test = Test {
+        name: "hello world".into(),
+        map: {
+            let mut map = BTreeMap::new();
+            map.insert("key".into(), -1);
+            map
+        }
+    };
+    let size = serialized_size(&test).unwrap();
+    let buf = to_vec(Vec::new(), &test).unwrap();
+    assert_eq!(size, buf.len());
+}
+
+#[test]
+fn test_core_stream_encoder() {
+    use cbor4ii::core::enc;
+
+    // an indefinite-length array of three elements
+    let mut array = enc::ArrayStreamEncoder::new(Vec::new()).unwrap();
+    enc::u64(&mut array.writer, 1).unwrap();
+    enc::u64(&mut array.writer, 2).unwrap();
+    enc::u64(&mut array.writer, 3).unwrap();
+    let buf = array.finish().unwrap();
+    let value: Vec<u64> = de(&buf, &vec![1u64, 2, 3]);
+    assert_eq!(value, vec![1, 2, 3]);
+
+    // an indefinite-length map of one entry
+    let mut map = enc::MapStreamEncoder::new(Vec::new()).unwrap();
+    enc::str(&mut map.writer, "a").unwrap();
+    enc::u64(&mut map.writer, 1).unwrap();
+    let buf = map.finish().unwrap();
+    let value: BTreeMap<String, u64> = de(&buf, &BTreeMap::new());
+    assert_eq!(value, BTreeMap::from([("a".to_string(), 1)]));
+
+    // a chunked byte string that decodes as if it were one contiguous string
+    let mut bytes = enc::BytesStreamEncoder::new(Vec::new()).unwrap();
+    bytes.push_chunk(b"abc").unwrap();
+    bytes.push_chunk(b"def").unwrap();
+    let buf = bytes.finish().unwrap();
+    let value: serde_bytes::ByteBuf = de(&buf, &serde_bytes::ByteBuf::from(Vec::new()));
+    assert_eq!(value.as_slice(), b"abcdef");
+
+    // a chunked text string likewise
+    let mut text = enc::TextStreamEncoder::new(Vec::new()).unwrap();
+    text.push_chunk("hello ").unwrap();
+    text.push_chunk("world").unwrap();
+    let buf = text.finish().unwrap();
+    let value: String = de(&buf, &String::new());
+    assert_eq!(value, "hello world");
+}
+
+#[test]
+fn test_serde_f16_decode() {
+    // additional info 25 (half-precision), raw bit patterns for 1.0, -2.0,
+    // +0.0, +inf, and the smallest positive subnormal (2^-24)
+    for (bits, expect) in [
+        (0x3c00u16, 1.0f64),
+        (0xc000, -2.0),
+        (0x0000, 0.0),
+        (0x7c00, f64::INFINITY),
+        (0x0001, 2f64.powi(-24))
+    ] {
+        let mut buf = vec![0xf9u8];
+        buf.extend_from_slice(&bits.to_be_bytes());
+        let value: f64 = de(&buf, &expect);
+        assert_eq!(value, expect);
+    }
+
+    // NaN can't be compared with ==, so check it separately
+    let buf = vec![0xf9, 0x7e, 0x00];
+    let value: f64 = cbor4ii::serde::from_slice(&buf).unwrap();
+    assert!(value.is_nan());
+}
+
+#[test]
+fn test_serde_small_floats_encode() {
+    use cbor4ii::serde::to_vec_small_floats;
+
+    // round-trips losslessly through f16, so it's encoded in 3 bytes
+    // (1 head byte + 2 body bytes) instead of f64's 1 + 8
+    let buf = to_vec_small_floats(Vec::new(), &1.5f64).unwrap();
+    assert_eq!(buf, vec![0xf9, 0x3e, 0x00]);
+    let value: f64 = de(&buf, &1.5);
+    assert_eq!(value, 1.5);
+
+    // doesn't round-trip losslessly, so it falls back to full precision
+    let buf = to_vec_small_floats(Vec::new(), &0.1f64).unwrap();
+    assert_eq!(buf[0], 0xfb); // f64 head
+    let value: f64 = de(&buf, &0.1);
+    assert_eq!(value, 0.1);
+
+    // same for f32
+    let buf = to_vec_small_floats(Vec::new(), &2.0f32).unwrap();
+    assert_eq!(buf, vec![0xf9, 0x40, 0x00]);
+    let value: f32 = de(&buf, &2.0f32);
+    assert_eq!(value, 2.0);
+}